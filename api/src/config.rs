@@ -0,0 +1,47 @@
+use std::env;
+
+/// Runtime configuration, assembled from the environment (and any `.env` file
+/// loaded at startup). Centralises everything that used to be hardcoded at the
+/// signing and verification sites: key material, token lifetimes, and the
+/// issuer/audience claims.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub database_url: String,
+    pub private_key_path: String,
+    pub public_key_path: String,
+    pub access_token_ttl: chrono::Duration,
+    pub refresh_token_ttl: chrono::Duration,
+    pub issuer: String,
+    pub audience: String,
+}
+
+impl Config {
+    /// Read configuration from the environment, falling back to sensible
+    /// defaults for everything except `DATABASE_URL`, which is required.
+    pub fn from_env() -> Self {
+        Self {
+            database_url: env::var("DATABASE_URL").expect("DATABASE_URL must be set"),
+            private_key_path: env::var("PRIVATE_KEY_PATH")
+                .unwrap_or_else(|_| "keys/private.pem".to_string()),
+            public_key_path: env::var("PUBLIC_KEY_PATH")
+                .unwrap_or_else(|_| "keys/public.pem".to_string()),
+            access_token_ttl: chrono::Duration::seconds(parse_env_secs(
+                "ACCESS_TOKEN_TTL_SECS",
+                15 * 60,
+            )),
+            refresh_token_ttl: chrono::Duration::seconds(parse_env_secs(
+                "REFRESH_TOKEN_TTL_SECS",
+                7 * 24 * 60 * 60,
+            )),
+            issuer: env::var("TOKEN_ISSUER").unwrap_or_else(|_| "bromance".to_string()),
+            audience: env::var("TOKEN_AUDIENCE").unwrap_or_else(|_| "bromance".to_string()),
+        }
+    }
+}
+
+fn parse_env_secs(key: &str, default: i64) -> i64 {
+    env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
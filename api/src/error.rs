@@ -4,19 +4,42 @@ use axum::{
     Json,
 };
 use serde_json::json;
+use thiserror::Error;
 
-#[derive(Debug)]
+#[derive(Debug, Error)]
 #[allow(dead_code)]
 pub enum AppError {
+    #[error("User already exists")]
+    UserExists,
+    #[error("Invalid credentials")]
+    InvalidCredentials,
+    #[error("Missing credentials")]
+    MissingCredentials,
+    #[error("Invalid token")]
+    InvalidToken,
+    #[error("Account is blocked")]
+    Blocked,
+    #[error("Database error")]
     Sqlx(sqlx::Error),
+    #[error("Password hashing error")]
     PasswordHash(argon2::password_hash::Error),
+    #[error("Token error")]
     Jwt(jsonwebtoken::errors::Error),
-    LoginFail,
-    AuthError(String),
 }
 
 impl From<sqlx::Error> for AppError {
     fn from(inner: sqlx::Error) -> Self {
+        // Only a unique violation on the `users` table means the email is
+        // taken; every other database error stays a generic `Sqlx`.
+        if let Some(db_err) = inner.as_database_error() {
+            // SQLite reports no table name, only a message such as
+            // "UNIQUE constraint failed: users.email".
+            let on_users = db_err.table() == Some("users")
+                || db_err.message().contains(" users.");
+            if db_err.is_unique_violation() && on_users {
+                return AppError::UserExists;
+            }
+        }
         AppError::Sqlx(inner)
     }
 }
@@ -33,41 +56,38 @@ impl From<jsonwebtoken::errors::Error> for AppError {
     }
 }
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
-        let (status, error_message) = match self {
-            AppError::Sqlx(e) => {
-                // Check for unique constraint violation
-                if let Some(db_err) = e.as_database_error() {
-                    if db_err.is_unique_violation() {
-                        return (
-                            StatusCode::CONFLICT,
-                            Json(json!({"error": "Email already exists"})),
-                        )
-                            .into_response();
-                    }
-                }
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "Database error".to_string(),
-                )
+impl AppError {
+    fn status(&self) -> StatusCode {
+        match self {
+            AppError::UserExists => StatusCode::CONFLICT,
+            AppError::InvalidCredentials | AppError::InvalidToken => StatusCode::UNAUTHORIZED,
+            AppError::MissingCredentials => StatusCode::BAD_REQUEST,
+            AppError::Blocked => StatusCode::FORBIDDEN,
+            AppError::Sqlx(_) | AppError::PasswordHash(_) | AppError::Jwt(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
             }
-            AppError::PasswordHash(_) => (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Password hashing error".to_string(),
-            ),
-            AppError::Jwt(_) => (StatusCode::INTERNAL_SERVER_ERROR, "Token error".to_string()),
-            AppError::LoginFail => (
-                StatusCode::UNAUTHORIZED,
-                "Invalid email or password".to_string(),
-            ),
-            AppError::AuthError(msg) => (StatusCode::BAD_REQUEST, msg),
-        };
-
-        let body = Json(json!({
-            "error": error_message,
-        }));
+        }
+    }
+}
 
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let body = Json(json!({ "error": self.to_string() }));
         (status, body).into_response()
     }
 }
+
+impl From<AppError> for tonic::Status {
+    fn from(err: AppError) -> Self {
+        use tonic::Code;
+        let code = match err {
+            AppError::UserExists => Code::AlreadyExists,
+            AppError::InvalidCredentials | AppError::InvalidToken => Code::Unauthenticated,
+            AppError::MissingCredentials => Code::InvalidArgument,
+            AppError::Blocked => Code::PermissionDenied,
+            AppError::Sqlx(_) | AppError::PasswordHash(_) | AppError::Jwt(_) => Code::Internal,
+        };
+        tonic::Status::new(code, err.to_string())
+    }
+}
@@ -1,8 +1,11 @@
+mod config;
+mod error;
 mod models;
 mod rest;
 mod rpc;
 
-use jsonwebtoken::EncodingKey;
+use config::Config;
+use jsonwebtoken::{DecodingKey, EncodingKey};
 use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
 use tonic::codec::CompressionEncoding;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
@@ -11,12 +14,16 @@ use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 pub struct AppState {
     pub db: SqlitePool,
     pub encoding_key: EncodingKey,
+    pub decoding_key: DecodingKey,
+    pub config: Config,
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenvy::dotenv().ok();
 
+    let config = Config::from_env();
+
     tracing_subscriber::registry()
         .with(tracing_subscriber::EnvFilter::new(
             std::env::var("RUST_LOG").unwrap_or_else(|_| "server=debug".into()),
@@ -24,20 +31,24 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    let db_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
     let pool = SqlitePoolOptions::new()
         .max_connections(5)
-        .connect(&db_url)
+        .connect(&config.database_url)
         .await
         .expect("Failed to connect to DB");
 
-    // Load private key
-    let encoding_key = EncodingKey::from_ed_pem(include_bytes!("../keys/private.pem"))
-        .expect("Failed to load private key");
+    // Load signing/verification keys from the configured paths
+    let private_pem = std::fs::read(&config.private_key_path).expect("Failed to read private key");
+    let public_pem = std::fs::read(&config.public_key_path).expect("Failed to read public key");
+    let encoding_key =
+        EncodingKey::from_ed_pem(&private_pem).expect("Failed to load private key");
+    let decoding_key = DecodingKey::from_ed_pem(&public_pem).expect("Failed to load public key");
 
     let app_state = AppState {
         db: pool,
         encoding_key,
+        decoding_key,
+        config,
     };
 
     // REST server on port 3000
@@ -49,13 +60,32 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // gRPC server on port 50051
     let grpc_addr = "[::0]:50051".parse().unwrap();
     tracing::info!("gRPC listening on {}", grpc_addr);
-    let auth_service = rpc::auth::AuthServiceImpl { state: app_state };
+    let auth_service = rpc::auth::AuthServiceImpl {
+        state: app_state.clone(),
+    };
     let auth_server =
         rpc::auth::auth_proto::auth_service_server::AuthServiceServer::new(auth_service)
             .accept_compressed(CompressionEncoding::Gzip)
             .send_compressed(CompressionEncoding::Gzip);
+
+    // The auth endpoints are deliberately public; protected services attach the
+    // interceptor so their handlers can read the authenticated `Claims` from
+    // request extensions.
+    let auth_interceptor =
+        rpc::AuthInterceptor::new(app_state.decoding_key.clone(), &app_state.config);
+    let user_service = rpc::user::UserServiceImpl {
+        state: app_state.clone(),
+    };
+    let user_server =
+        rpc::auth::auth_proto::user_service_server::UserServiceServer::with_interceptor(
+            user_service,
+            auth_interceptor,
+        )
+        .accept_compressed(CompressionEncoding::Gzip)
+        .send_compressed(CompressionEncoding::Gzip);
     let grpc_server = tonic::transport::Server::builder()
         .add_service(auth_server)
+        .add_service(user_server)
         .serve_with_shutdown(grpc_addr, shutdown_signal());
 
     // Run both servers concurrently
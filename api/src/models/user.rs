@@ -1,4 +1,14 @@
+use axum::extract::{FromRef, FromRequestParts};
+use axum::http::request::Parts;
+use axum_extra::extract::cookie::CookieJar;
+use jsonwebtoken::{decode, encode, Algorithm, Header, Validation};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+
+use crate::config::Config;
+use crate::error::AppError;
+use crate::AppState;
 
 #[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
 pub struct User {
@@ -7,10 +17,286 @@ pub struct User {
     #[serde(skip)]
     pub password_hash: String,
     pub created_at: chrono::NaiveDateTime,
+    pub blocked: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
-    pub sub: String, // email
+    pub sub: String, // user id
     pub exp: usize,
+    pub iss: String,
+    pub aud: String,
+}
+
+impl Claims {
+    /// Build the access-token claims for `sub`, stamping the configured issuer,
+    /// audience, and access-token TTL.
+    pub fn access(config: &Config, sub: String) -> Self {
+        Self {
+            sub,
+            exp: (chrono::Utc::now() + config.access_token_ttl).timestamp() as usize,
+            iss: config.issuer.clone(),
+            aud: config.audience.clone(),
+        }
+    }
+}
+
+/// Build the `Validation` used everywhere access tokens are verified so the
+/// issuer/audience checks stay in lockstep with the minting side.
+pub fn access_validation(config: &Config) -> Validation {
+    let mut validation = Validation::new(Algorithm::EdDSA);
+    validation.set_issuer(&[&config.issuer]);
+    validation.set_audience(&[&config.audience]);
+    validation
+}
+
+/// Sign an access token for `user_id` using the configured key and TTL. This
+/// is the single signing path shared by every transport.
+pub fn mint_access_token(state: &AppState, user_id: i64) -> Result<String, AppError> {
+    let claims = Claims::access(&state.config, user_id.to_string());
+    let token = encode(
+        &Header::new(Algorithm::EdDSA),
+        &claims,
+        &state.encoding_key,
+    )?;
+    Ok(token)
+}
+
+/// Validate the `Authorization: Bearer` token on the request and hand the
+/// decoded `Claims` to the handler. Expired or malformed tokens are rejected
+/// as `AppError::InvalidToken`.
+#[axum::async_trait]
+impl<S> FromRequestParts<S> for Claims
+where
+    S: Send + Sync,
+    crate::AppState: FromRef<S>,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let app_state = crate::AppState::from_ref(state);
+
+        // Prefer the `Authorization: Bearer` header, then fall back to the
+        // `access_token` cookie set by the login handlers.
+        let header_token = parts
+            .headers
+            .get(axum::http::header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(|value| value.to_string());
+
+        let token = match header_token {
+            Some(token) => token,
+            None => {
+                let jar = CookieJar::from_request_parts(parts, state)
+                    .await
+                    .unwrap_or_default();
+                jar.get("access_token")
+                    .map(|cookie| cookie.value().to_string())
+                    .ok_or(AppError::MissingCredentials)?
+            }
+        };
+
+        let data = decode::<Claims>(
+            &token,
+            &app_state.decoding_key,
+            &access_validation(&app_state.config),
+        )
+        .map_err(|_| AppError::InvalidToken)?;
+
+        Ok(data.claims)
+    }
+}
+
+/// A long-lived, opaque refresh token. Only the SHA-256 hash of the token is
+/// ever persisted; the plaintext lives solely in the response to the client.
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct RefreshToken {
+    pub id: i64,
+    pub user_id: i64,
+    #[serde(skip)]
+    pub token_hash: String,
+    pub expires_at: chrono::NaiveDateTime,
+    pub revoked: bool,
+    pub replaced_by: Option<i64>,
+}
+
+/// Result of rotating a refresh token: the owning user plus the freshly minted
+/// plaintext token that replaces the presented one.
+pub struct Rotation {
+    pub user_id: i64,
+    pub refresh_token: String,
+}
+
+fn generate_refresh_token() -> String {
+    use argon2::password_hash::rand_core::{OsRng, RngCore};
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+fn hash_refresh_token(token: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(token.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+impl RefreshToken {
+    /// Mint a new refresh token for `user_id`, persist its hash, and return the
+    /// plaintext to hand back to the caller.
+    pub async fn issue(
+        pool: &SqlitePool,
+        user_id: i64,
+        ttl: chrono::Duration,
+    ) -> Result<String, AppError> {
+        let token = generate_refresh_token();
+        let token_hash = hash_refresh_token(&token);
+        let expires_at = chrono::Utc::now() + ttl;
+        sqlx::query(
+            "INSERT INTO refresh_tokens (user_id, token_hash, expires_at, revoked) VALUES (?, ?, ?, 0)",
+        )
+        .bind(user_id)
+        .bind(&token_hash)
+        .bind(expires_at.naive_utc())
+        .execute(pool)
+        .await?;
+        Ok(token)
+    }
+
+    /// Exchange a presented refresh token for a fresh pair. Revokes the
+    /// presented token and links it to its replacement. If the presented token
+    /// was already revoked, treat it as a theft signal and revoke every token
+    /// in the user's chain before refusing.
+    pub async fn rotate(
+        pool: &SqlitePool,
+        presented: &str,
+        ttl: chrono::Duration,
+    ) -> Result<Rotation, AppError> {
+        let token_hash = hash_refresh_token(presented);
+
+        let mut tx = pool.begin().await?;
+
+        let row =
+            sqlx::query_as::<_, RefreshToken>("SELECT * FROM refresh_tokens WHERE token_hash = ?")
+                .bind(&token_hash)
+                .fetch_optional(&mut *tx)
+                .await?
+                .ok_or(AppError::InvalidToken)?;
+
+        // A token issued before the account was blocked must stop working
+        // immediately, not just once the access token it backs expires.
+        let blocked = sqlx::query_scalar::<_, bool>("SELECT blocked FROM users WHERE id = ?")
+            .bind(row.user_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        if blocked {
+            return Err(AppError::Blocked);
+        }
+
+        // Reuse of an already-revoked token is a theft signal: burn the chain.
+        if row.revoked {
+            sqlx::query("UPDATE refresh_tokens SET revoked = 1 WHERE user_id = ?")
+                .bind(row.user_id)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            return Err(AppError::InvalidToken);
+        }
+
+        if row.expires_at < chrono::Utc::now().naive_utc() {
+            return Err(AppError::InvalidToken);
+        }
+
+        let token = generate_refresh_token();
+        let new_hash = hash_refresh_token(&token);
+        let expires_at = chrono::Utc::now() + ttl;
+        let new_id = sqlx::query_scalar::<_, i64>(
+            "INSERT INTO refresh_tokens (user_id, token_hash, expires_at, revoked) VALUES (?, ?, ?, 0) RETURNING id",
+        )
+        .bind(row.user_id)
+        .bind(&new_hash)
+        .bind(expires_at.naive_utc())
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query("UPDATE refresh_tokens SET revoked = 1, replaced_by = ? WHERE id = ?")
+            .bind(new_id)
+            .bind(row.id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(Rotation {
+            user_id: row.user_id,
+            refresh_token: token,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn test_pool() -> SqlitePool {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        sqlx::query(
+            "CREATE TABLE users (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                email TEXT NOT NULL UNIQUE,
+                password_hash TEXT NOT NULL,
+                created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                blocked BOOLEAN NOT NULL DEFAULT 0
+            )",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "CREATE TABLE refresh_tokens (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_id INTEGER NOT NULL REFERENCES users (id),
+                token_hash TEXT NOT NULL UNIQUE,
+                expires_at DATETIME NOT NULL,
+                revoked BOOLEAN NOT NULL DEFAULT 0,
+                replaced_by INTEGER REFERENCES refresh_tokens (id)
+            )",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn rotation_burns_the_chain_on_reuse() {
+        let pool = test_pool().await;
+        let user_id = sqlx::query_scalar::<_, i64>(
+            "INSERT INTO users (email, password_hash) VALUES (?, ?) RETURNING id",
+        )
+        .bind("reuse@example.com")
+        .bind("hash")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        let ttl = chrono::Duration::days(7);
+        let first = RefreshToken::issue(&pool, user_id, ttl).await.unwrap();
+
+        // Normal rotation: the presented token is exchanged for a fresh pair.
+        let rotation = RefreshToken::rotate(&pool, &first, ttl).await.unwrap();
+        assert_eq!(rotation.user_id, user_id);
+        assert_ne!(rotation.refresh_token, first);
+
+        // Replaying the now-revoked `first` token is a theft signal: it must
+        // be rejected, and the whole chain (including the token it was
+        // rotated into) must be burned as a result.
+        let replay = RefreshToken::rotate(&pool, &first, ttl).await;
+        assert!(matches!(replay, Err(AppError::InvalidToken)));
+
+        // The latest, legitimately-issued token must now be rejected too,
+        // since reuse detection revoked the entire chain.
+        let latest = RefreshToken::rotate(&pool, &rotation.refresh_token, ttl).await;
+        assert!(matches!(latest, Err(AppError::InvalidToken)));
+    }
 }
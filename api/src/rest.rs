@@ -1,50 +1,114 @@
-use axum::{extract::State, http::StatusCode, response::IntoResponse, routing::post, Json, Router};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post},
+    Json, Router,
+};
+use axum_extra::either::Either;
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
+use axum_extra::headers::{authorization::Basic, Authorization};
+use axum_extra::TypedHeader;
 use serde::{Deserialize, Serialize};
+use utoipa::{OpenApi, ToSchema};
+use utoipa_swagger_ui::SwaggerUi;
 
+use crate::error::AppError;
+use crate::models::user::{Claims, User};
 use crate::AppState;
 
 // Request/Response types
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct LoginRequest {
     pub username: String,
     pub password: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct LoginResponse {
     pub token: String,
+    pub refresh_token: String,
     pub success: bool,
     pub message: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Deserialize, ToSchema)]
 pub struct RegisterRequest {
     pub username: String,
     pub password: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct RegisterResponse {
     pub success: bool,
     pub message: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct ErrorResponse {
     pub error: String,
 }
 
+/// OpenAPI description of the `/api/auth/login` and `/api/auth/register`
+/// routes; `/api/auth/refresh` and `/api/auth/logout` are not included. Each
+/// error response is documented with the body its handler actually returns.
+#[derive(OpenApi)]
+#[openapi(
+    paths(login, register),
+    components(schemas(
+        LoginRequest,
+        LoginResponse,
+        RegisterRequest,
+        RegisterResponse,
+        ErrorResponse
+    )),
+    tags((name = "auth", description = "Authentication endpoints"))
+)]
+pub struct ApiDoc;
+
 // Handlers
+#[utoipa::path(
+    post,
+    path = "/api/auth/login",
+    request_body(
+        content = Option<LoginRequest>,
+        description = "Omit the body and send credentials via an `Authorization: Basic` header instead",
+        content_type = "application/json"
+    ),
+    responses(
+        (status = 200, description = "Login successful", body = LoginResponse),
+        (status = 400, description = "Missing credentials: no JSON body and no Basic auth header", body = ErrorResponse),
+        (status = 401, description = "Invalid credentials", body = ErrorResponse),
+        (status = 403, description = "Account is blocked", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    tag = "auth"
+)]
 pub async fn login(
     State(state): State<AppState>,
-    Json(req): Json<LoginRequest>,
+    credentials: Option<Either<TypedHeader<Authorization<Basic>>, Json<LoginRequest>>>,
 ) -> impl IntoResponse {
     use argon2::{Argon2, PasswordHash, PasswordVerifier};
 
+    // Accept credentials from a `Basic` header or the JSON body, whichever the
+    // client supplies; reject only when neither channel carries them.
+    let (username, password) = match credentials {
+        Some(Either::E1(TypedHeader(Authorization(basic)))) => {
+            (basic.username().to_string(), basic.password().to_string())
+        }
+        Some(Either::E2(Json(req))) => (req.username, req.password),
+        None => return AppError::MissingCredentials.into_response(),
+    };
+
     // Find user
     let user =
         match sqlx::query_as::<_, crate::models::user::User>("SELECT * FROM users WHERE email = ?")
-            .bind(&req.username)
+            .bind(&username)
             .fetch_optional(&state.db)
             .await
         {
@@ -70,6 +134,11 @@ pub async fn login(
             }
         };
 
+    // Reject blocked accounts before touching the password.
+    if user.blocked {
+        return AppError::Blocked.into_response();
+    }
+
     // Verify password
     let parsed_hash = match PasswordHash::new(&user.password_hash) {
         Ok(h) => h,
@@ -85,7 +154,7 @@ pub async fn login(
     };
 
     if Argon2::default()
-        .verify_password(req.password.as_bytes(), &parsed_hash)
+        .verify_password(password.as_bytes(), &parsed_hash)
         .is_err()
     {
         return (
@@ -98,21 +167,31 @@ pub async fn login(
     }
 
     // Generate JWT
-    use jsonwebtoken::{encode, Header};
-
-    let claims = crate::models::user::Claims {
-        sub: user.id.to_string(),
-        exp: (chrono::Utc::now() + chrono::Duration::days(7)).timestamp() as usize,
+    let token = match crate::models::user::mint_access_token(&state, user.id) {
+        Ok(t) => t,
+        Err(e) => {
+            tracing::error!("JWT error: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: "Internal server error".to_string(),
+                }),
+            )
+                .into_response();
+        }
     };
 
-    let token = match encode(
-        &Header::new(jsonwebtoken::Algorithm::EdDSA),
-        &claims,
-        &state.encoding_key,
-    ) {
+    // Issue the opaque refresh token that lets the client renew the access JWT.
+    let refresh_token = match crate::models::user::RefreshToken::issue(
+        &state.db,
+        user.id,
+        state.config.refresh_token_ttl,
+    )
+    .await
+    {
         Ok(t) => t,
         Err(e) => {
-            tracing::error!("JWT error: {}", e);
+            tracing::error!("Refresh token error: {:?}", e);
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(ErrorResponse {
@@ -123,17 +202,87 @@ pub async fn login(
         }
     };
 
+    // Also hand the access token back as a hardened cookie so browser clients
+    // don't have to store the bearer token themselves.
+    let jar = CookieJar::new().add(access_cookie(&state, token.clone()));
+
     (
-        StatusCode::OK,
-        Json(LoginResponse {
-            token,
+        jar,
+        (
+            StatusCode::OK,
+            Json(LoginResponse {
+                token,
+                refresh_token,
+                success: true,
+                message: "Login successful".to_string(),
+            }),
+        ),
+    )
+        .into_response()
+}
+
+// Build the `access_token` cookie: HttpOnly + Secure + SameSite=Strict, with a
+// `Max-Age` matching the access-token TTL.
+fn access_cookie(state: &AppState, token: String) -> Cookie<'static> {
+    Cookie::build(("access_token", token))
+        .http_only(true)
+        .secure(true)
+        .same_site(SameSite::Strict)
+        .path("/")
+        .max_age(time::Duration::seconds(
+            state.config.access_token_ttl.num_seconds(),
+        ))
+        .build()
+}
+
+// Clear the access-token cookie. The removal cookie must match the original
+// cookie's path or the jar won't actually clear it client-side.
+pub async fn logout(jar: CookieJar) -> impl IntoResponse {
+    let jar = jar.remove(Cookie::build(("access_token", "")).path("/"));
+    (
+        jar,
+        Json(RegisterResponse {
             success: true,
-            message: "Login successful".to_string(),
+            message: "Logged out".to_string(),
         }),
     )
-        .into_response()
 }
 
+// Exchange a valid refresh token for a fresh access + refresh pair. Rotation
+// revokes the presented token; presenting an already-revoked token burns the
+// whole chain for that user (reuse detection).
+pub async fn refresh(
+    State(state): State<AppState>,
+    Json(req): Json<RefreshRequest>,
+) -> Result<Json<LoginResponse>, AppError> {
+    let rotation = crate::models::user::RefreshToken::rotate(
+        &state.db,
+        &req.refresh_token,
+        state.config.refresh_token_ttl,
+    )
+    .await?;
+
+    let token = crate::models::user::mint_access_token(&state, rotation.user_id)?;
+
+    Ok(Json(LoginResponse {
+        token,
+        refresh_token: rotation.refresh_token,
+        success: true,
+        message: "Token refreshed".to_string(),
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/auth/register",
+    request_body = RegisterRequest,
+    responses(
+        (status = 201, description = "Registration successful", body = RegisterResponse),
+        (status = 409, description = "User already exists", body = RegisterResponse),
+        (status = 500, description = "Internal server error", body = RegisterResponse)
+    ),
+    tag = "auth"
+)]
 pub async fn register(
     State(state): State<AppState>,
     Json(req): Json<RegisterRequest>,
@@ -208,9 +357,33 @@ pub async fn register(
     }
 }
 
+// Return the authenticated user, resolved from the `sub` claim of the
+// validated access token.
+pub async fn me(
+    claims: Claims,
+    State(state): State<AppState>,
+) -> Result<Json<User>, AppError> {
+    let id: i64 = claims
+        .sub
+        .parse()
+        .map_err(|_| AppError::InvalidToken)?;
+
+    let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = ?")
+        .bind(id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or(AppError::InvalidCredentials)?;
+
+    Ok(Json(user))
+}
+
 pub fn router(state: AppState) -> Router {
     Router::new()
         .route("/api/auth/login", post(login))
         .route("/api/auth/register", post(register))
+        .route("/api/auth/refresh", post(refresh))
+        .route("/api/auth/logout", post(logout))
+        .route("/api/users/me", get(me))
+        .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
         .with_state(state)
 }
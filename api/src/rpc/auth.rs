@@ -1,10 +1,10 @@
-use crate::models::user::{Claims, User};
+use crate::error::AppError;
+use crate::models::user::{mint_access_token, RefreshToken, User};
 use crate::AppState;
 use argon2::{
     password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
     Argon2,
 };
-use jsonwebtoken::{encode, Header};
 use tonic::{Request, Response, Status};
 
 pub mod auth_proto {
@@ -12,7 +12,9 @@ pub mod auth_proto {
 }
 
 use auth_proto::auth_service_server::AuthService;
-use auth_proto::{LoginRequest, LoginResponse, RegisterRequest, RegisterResponse};
+use auth_proto::{
+    LoginRequest, LoginResponse, RefreshRequest, RegisterRequest, RegisterResponse,
+};
 
 pub struct AuthServiceImpl {
     pub state: AppState,
@@ -28,42 +30,63 @@ impl AuthService for AuthServiceImpl {
 
         let user = sqlx::query_as!(
             User,
-            "SELECT id as \"id!\", email as \"email!\", password_hash as \"password_hash!\", created_at as \"created_at!\" FROM users WHERE email = ?",
+            "SELECT id as \"id!\", email as \"email!\", password_hash as \"password_hash!\", created_at as \"created_at!\", blocked as \"blocked!\" FROM users WHERE email = ?",
             req.username
         )
         .fetch_optional(&self.state.db)
         .await
-        .map_err(|e| Status::internal(e.to_string()))?
-        .ok_or(Status::unauthenticated("Invalid credentials"))?;
+        .map_err(AppError::from)?
+        .ok_or(AppError::InvalidCredentials)?;
+
+        if user.blocked {
+            return Err(AppError::Blocked.into());
+        }
 
-        let parsed_hash =
-            PasswordHash::new(&user.password_hash).map_err(|e| Status::internal(e.to_string()))?;
+        let parsed_hash = PasswordHash::new(&user.password_hash).map_err(AppError::from)?;
 
         Argon2::default()
             .verify_password(req.password.as_bytes(), &parsed_hash)
-            .map_err(|_| Status::unauthenticated("Invalid credentials"))?;
-
-        let expiration = chrono::Utc::now()
-            .checked_add_signed(chrono::Duration::hours(24))
-            .ok_or_else(|| Status::internal("valid timestamp"))?
-            .timestamp();
+            .map_err(|_| AppError::InvalidCredentials)?;
 
-        let claims = Claims {
-            sub: user.email,
-            exp: expiration as usize,
-        };
+        let token = mint_access_token(&self.state, user.id).map_err(Status::from)?;
 
-        let header = Header::new(jsonwebtoken::Algorithm::EdDSA);
-        let token = encode(&header, &claims, &self.state.encoding_key)
-            .map_err(|e| Status::internal(e.to_string()))?;
+        let refresh_token =
+            RefreshToken::issue(&self.state.db, user.id, self.state.config.refresh_token_ttl)
+                .await
+                .map_err(Status::from)?;
 
         Ok(Response::new(LoginResponse {
             token,
+            refresh_token,
             success: true,
             message: "Login successful".to_string(),
         }))
     }
 
+    async fn refresh(
+        &self,
+        request: Request<RefreshRequest>,
+    ) -> Result<Response<LoginResponse>, Status> {
+        let req = request.into_inner();
+
+        let rotation = RefreshToken::rotate(
+            &self.state.db,
+            &req.refresh_token,
+            self.state.config.refresh_token_ttl,
+        )
+        .await
+        .map_err(Status::from)?;
+
+        let token = mint_access_token(&self.state, rotation.user_id).map_err(Status::from)?;
+
+        Ok(Response::new(LoginResponse {
+            token,
+            refresh_token: rotation.refresh_token,
+            success: true,
+            message: "Token refreshed".to_string(),
+        }))
+    }
+
     async fn register(
         &self,
         request: Request<RegisterRequest>,
@@ -73,18 +96,18 @@ impl AuthService for AuthServiceImpl {
         let argon2 = Argon2::default();
         let password_hash = argon2
             .hash_password(req.password.as_bytes(), &salt)
-            .map_err(|e| Status::internal(e.to_string()))?
+            .map_err(AppError::from)?
             .to_string();
 
         let _user = sqlx::query_as!(
             User,
-            "INSERT INTO users (email, password_hash) VALUES (?, ?) RETURNING id as \"id!\", email as \"email!\", password_hash as \"password_hash!\", created_at as \"created_at!\"",
+            "INSERT INTO users (email, password_hash) VALUES (?, ?) RETURNING id as \"id!\", email as \"email!\", password_hash as \"password_hash!\", created_at as \"created_at!\", blocked as \"blocked!\"",
             req.username,
             password_hash
         )
         .fetch_one(&self.state.db)
         .await
-        .map_err(|e| Status::internal(e.to_string()))?;
+        .map_err(AppError::from)?;
 
         Ok(Response::new(RegisterResponse {
             success: true,
@@ -0,0 +1,44 @@
+pub mod auth;
+pub mod user;
+
+use jsonwebtoken::{decode, DecodingKey, Validation};
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+
+use crate::models::user::{access_validation, Claims};
+use crate::config::Config;
+
+/// gRPC analogue of the axum `Claims` extractor: validates the
+/// `authorization: Bearer` metadata and stashes the decoded `Claims` in the
+/// request extensions so handlers can read the authenticated user id.
+#[derive(Clone)]
+pub struct AuthInterceptor {
+    pub decoding_key: DecodingKey,
+    pub validation: Validation,
+}
+
+impl AuthInterceptor {
+    pub fn new(decoding_key: DecodingKey, config: &Config) -> Self {
+        Self {
+            decoding_key,
+            validation: access_validation(config),
+        }
+    }
+}
+
+impl Interceptor for AuthInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let token = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| Status::unauthenticated("Missing bearer token"))?;
+
+        let data = decode::<Claims>(token, &self.decoding_key, &self.validation)
+            .map_err(|_| Status::unauthenticated("Invalid token"))?;
+
+        request.extensions_mut().insert(data.claims);
+        Ok(request)
+    }
+}
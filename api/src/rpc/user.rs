@@ -0,0 +1,45 @@
+use crate::error::AppError;
+use crate::models::user::{Claims, User};
+use crate::AppState;
+use tonic::{Request, Response, Status};
+
+use super::auth::auth_proto::user_service_server::UserService;
+use super::auth::auth_proto::{MeRequest, MeResponse};
+
+pub struct UserServiceImpl {
+    pub state: AppState,
+}
+
+#[tonic::async_trait]
+impl UserService for UserServiceImpl {
+    /// gRPC analogue of `GET /api/users/me`: the `AuthInterceptor` has already
+    /// validated the bearer token and left the decoded `Claims` in the request
+    /// extensions, so the handler just resolves the user from the `sub` claim.
+    async fn me(&self, request: Request<MeRequest>) -> Result<Response<MeResponse>, Status> {
+        let claims = request
+            .extensions()
+            .get::<Claims>()
+            .ok_or_else(|| Status::unauthenticated("Missing claims"))?;
+
+        let id: i64 = claims
+            .sub
+            .parse()
+            .map_err(|_| Status::unauthenticated("Invalid subject"))?;
+
+        let user = sqlx::query_as!(
+            User,
+            "SELECT id as \"id!\", email as \"email!\", password_hash as \"password_hash!\", created_at as \"created_at!\", blocked as \"blocked!\" FROM users WHERE id = ?",
+            id
+        )
+        .fetch_optional(&self.state.db)
+        .await
+        .map_err(AppError::from)
+        .map_err(Status::from)?
+        .ok_or_else(|| Status::not_found("User not found"))?;
+
+        Ok(Response::new(MeResponse {
+            id: user.id,
+            email: user.email,
+        }))
+    }
+}